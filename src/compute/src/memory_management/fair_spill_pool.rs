@@ -0,0 +1,197 @@
+// Copyright 2023 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [`MemoryPool`] that keeps one greedy batch query from starving streaming caches.
+//!
+//! `GlobalMemoryManager::run`'s doc comment distinguishes two failure modes: "if batch exceeds,
+//! kill running query" and "if streaming exceeds, evict cache by watermark". [`FairSpillPool`]
+//! turns the first case into something less blunt: streaming state (which can only shed bytes by
+//! watermark eviction, not by spilling) gets first claim on the budget, and whatever remains is
+//! split evenly across the batch operators that *can* spill, so growth past a query's fair share
+//! fails fast and lets that one query spill instead of evicting cache or failing its neighbours.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use risingwave_common::error::{ErrorCode, Result, RwError};
+
+use super::memory_pool::{MemoryPool, MemoryReservation};
+
+#[derive(Debug, Default)]
+struct ConsumerState {
+    /// Bytes reserved by spillable consumers, keyed by reservation name.
+    spillable: HashMap<String, usize>,
+    /// Bytes reserved by unspillable consumers, keyed by reservation name.
+    unspillable: HashMap<String, usize>,
+}
+
+impl ConsumerState {
+    fn unspillable_total(&self) -> usize {
+        self.unspillable.values().sum()
+    }
+
+    fn num_spillable(&self) -> usize {
+        self.spillable.len()
+    }
+}
+
+/// A [`MemoryPool`] that partitions `limit` between unspillable consumers (streaming state) and
+/// spillable consumers (batch sort/hash-agg and the like).
+///
+/// Unspillable consumers are granted whatever they ask for, up to the whole budget, since the
+/// only way to shed their memory is the LRU watermark path outside this pool. The remainder is
+/// divided equally across the currently registered spillable consumers: a spillable
+/// reservation's `try_grow` fails once its own size would exceed `remaining / num_spillable`,
+/// which is recomputed on every registration and deregistration.
+pub struct FairSpillPool {
+    limit: usize,
+    state: Mutex<ConsumerState>,
+    /// Mirrors `state`'s total for cheap reads from `reserved()`.
+    reserved: AtomicU64,
+}
+
+impl fmt::Debug for FairSpillPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FairSpillPool")
+            .field("limit", &self.limit)
+            .field("reserved", &self.reserved.load(Ordering::SeqCst))
+            .finish()
+    }
+}
+
+impl FairSpillPool {
+    pub fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            state: Mutex::new(ConsumerState::default()),
+            reserved: AtomicU64::new(0),
+        })
+    }
+
+    /// Registers a consumer, marking whether it is allowed to spill to disk rather than only
+    /// being evictable via the LRU watermark.
+    pub fn register_consumer(self: Arc<Self>, name: String, can_spill: bool) -> MemoryReservation {
+        let mut state = self.state.lock().unwrap();
+        if can_spill {
+            state.spillable.insert(name.clone(), 0);
+        } else {
+            state.unspillable.insert(name.clone(), 0);
+        }
+        drop(state);
+        // Construct the reservation directly rather than going through
+        // `<FairSpillPool as MemoryPool>::register_consumer`: that trait method calls back into
+        // this inherent method (defaulting `can_spill` to `true`), and an inherent method always
+        // wins over a trait method at the same `Type::method` path, so bouncing through it here
+        // would recurse forever instead of ever returning.
+        MemoryReservation::new(name, self)
+    }
+
+    /// Removes a consumer's accounting entirely, recomputing the fair share for the remaining
+    /// spillable consumers. Called automatically from `MemoryReservation::drop` via the
+    /// `MemoryPool::deregister` trait method below, once a consumer's reservation goes out of
+    /// scope.
+    pub fn deregister(&self, name: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.spillable.remove(name);
+        state.unspillable.remove(name);
+    }
+
+    /// The budget each spillable consumer is currently entitled to: the leftover after
+    /// unspillable consumers' footprint, split evenly across the active spillable consumers.
+    fn spillable_share(&self, state: &ConsumerState) -> usize {
+        let remaining = self.limit.saturating_sub(state.unspillable_total());
+        let n = state.num_spillable().max(1);
+        remaining / n
+    }
+}
+
+impl MemoryPool for FairSpillPool {
+    fn register_consumer(self: Arc<Self>, name: String) -> MemoryReservation {
+        // Default new consumers to spillable; callers that need unspillable accounting should go
+        // through `FairSpillPool::register_consumer` directly.
+        FairSpillPool::register_consumer(self, name, true)
+    }
+
+    fn try_grow(&self, reservation: &mut MemoryReservation, additional: usize) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let name = reservation.name().to_owned();
+
+        if let Some(used) = state.unspillable.get(&name).copied() {
+            let new_used = used + additional;
+            if state.unspillable_total() - used + new_used > self.limit {
+                return Err(RwError::from(ErrorCode::InternalError(format!(
+                    "unspillable reservation {:?} cannot grow by {} bytes: pool limit {} exceeded",
+                    name, additional, self.limit
+                ))));
+            }
+            state.unspillable.insert(name, new_used);
+        } else {
+            let share = self.spillable_share(&state);
+            let used = *state.spillable.get(&name).unwrap_or(&0);
+            let new_used = used + additional;
+            if new_used > share {
+                return Err(RwError::from(ErrorCode::InternalError(format!(
+                    "spillable reservation {:?} cannot grow by {} bytes: would exceed its fair \
+                     share of {} bytes ({} consumers); spill instead",
+                    name,
+                    additional,
+                    share,
+                    state.num_spillable()
+                ))));
+            }
+            state.spillable.insert(name, new_used);
+        }
+
+        self.reserved.fetch_add(additional as u64, Ordering::SeqCst);
+        reservation.set_size(reservation.size() + additional);
+        Ok(())
+    }
+
+    fn grow(&self, reservation: &mut MemoryReservation, additional: usize) {
+        let mut state = self.state.lock().unwrap();
+        let name = reservation.name().to_owned();
+        if let Some(used) = state.unspillable.get_mut(&name) {
+            *used += additional;
+        } else {
+            *state.spillable.entry(name).or_insert(0) += additional;
+        }
+        self.reserved.fetch_add(additional as u64, Ordering::SeqCst);
+        reservation.set_size(reservation.size() + additional);
+    }
+
+    fn shrink(&self, reservation: &mut MemoryReservation, shrink: usize) {
+        let mut state = self.state.lock().unwrap();
+        let name = reservation.name().to_owned();
+        if let Some(used) = state.unspillable.get_mut(&name) {
+            *used = used.saturating_sub(shrink);
+        } else if let Some(used) = state.spillable.get_mut(&name) {
+            *used = used.saturating_sub(shrink);
+        }
+        self.reserved.fetch_sub(shrink as u64, Ordering::SeqCst);
+        reservation.set_size(reservation.size() - shrink);
+    }
+
+    fn reserved(&self) -> usize {
+        self.reserved.load(Ordering::SeqCst) as usize
+    }
+
+    fn deregister(&self, name: &str) {
+        // Unlike `register_consumer` above, the inherent `deregister` never calls back into this
+        // trait method, so forwarding to it by its explicit path is safe from the recursion that
+        // bit `register_consumer`.
+        FairSpillPool::deregister(self, name)
+    }
+}
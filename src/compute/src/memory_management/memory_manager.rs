@@ -19,6 +19,7 @@ use risingwave_batch::task::BatchManager;
 use risingwave_stream::executor::monitor::StreamingMetrics;
 use risingwave_stream::task::LocalStreamManager;
 
+use super::memory_pool::{GreedyMemoryPool, MemoryPool};
 use super::policy::MemoryControlPolicy;
 
 /// The minimal memory requirement of computing tasks in megabytes.
@@ -41,6 +42,10 @@ pub struct GlobalMemoryManager {
     metrics: Arc<StreamingMetrics>,
     /// The memory control policy for computing tasks.
     memory_control_policy: MemoryControlPolicy,
+    /// Pool that batch and streaming tasks reserve memory from before growing. Admission is
+    /// enforced here, up front; the watermark-eviction path in `run` remains as a secondary,
+    /// reactive policy on top of it.
+    memory_pool: Arc<dyn MemoryPool>,
 }
 
 pub type GlobalMemoryManagerRef = Arc<GlobalMemoryManager>;
@@ -67,6 +72,7 @@ impl GlobalMemoryManager {
             barrier_interval_ms,
             metrics,
             memory_control_policy,
+            memory_pool: GreedyMemoryPool::new(total_compute_memory_bytes),
         })
     }
 
@@ -74,6 +80,12 @@ impl GlobalMemoryManager {
         self.watermark_epoch.clone()
     }
 
+    /// The pool that `BatchManager` and `LocalStreamManager` reserve memory from. Both managers
+    /// are handed the same `Arc`, so a reservation registered by one is visible to the other.
+    pub fn memory_pool(&self) -> Arc<dyn MemoryPool> {
+        self.memory_pool.clone()
+    }
+
     // FIXME: remove such limitation after #7180
     /// Jemalloc is not supported on Windows, because of tikv-jemalloc's own reasons.
     /// See the comments for the macro `enable_jemalloc_on_linux!()`
@@ -96,6 +108,12 @@ impl GlobalMemoryManager {
 
         use crate::memory_management::policy::MemoryControlStats;
 
+        // Hand both managers the shared pool so their operators can register reservations and
+        // grow them via `try_grow` up front, and learn immediately when the pool is exhausted,
+        // instead of only being caught after the fact by the reactive eviction loop below.
+        batch_manager.set_memory_pool(self.memory_pool());
+        stream_manager.set_memory_pool(self.memory_pool());
+
         let mut tick_interval =
             tokio::time::interval(Duration::from_millis(self.barrier_interval_ms as u64));
         let mut memory_control_stats = MemoryControlStats {
@@ -0,0 +1,199 @@
+// Copyright 2023 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A reservation-based memory accounting abstraction for computing tasks.
+//!
+//! Unlike the watermark-eviction path in [`super::policy`], which discovers an overshoot only
+//! after the fact on the next tick, a [`MemoryPool`] is consulted *before* an operator grows its
+//! footprint. This makes admission control deterministic: the operator either gets the bytes it
+//! asked for, or it learns immediately that it must spill or abort.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use risingwave_common::error::{ErrorCode, Result, RwError};
+
+/// A pool of compute memory shared by batch and streaming tasks.
+///
+/// Consumers register once via [`MemoryPool::register_consumer`] and then request additional
+/// bytes through [`MemoryPool::try_grow`] on the [`MemoryReservation`] they were handed back.
+/// Implementations must be safe to share behind an `Arc` and call concurrently from many tasks.
+pub trait MemoryPool: fmt::Debug + Send + Sync {
+    /// Registers a new consumer with the pool and returns an empty reservation for it to grow.
+    fn register_consumer(self: Arc<Self>, name: String) -> MemoryReservation;
+
+    /// Attempts to grow `reservation` by `additional` bytes. On success, `reservation`'s size is
+    /// updated; on failure, the reservation is left unchanged and the caller should spill or
+    /// abort rather than proceed with the allocation.
+    fn try_grow(&self, reservation: &mut MemoryReservation, additional: usize) -> Result<()>;
+
+    /// Grows `reservation` by `additional` bytes unconditionally, even past the pool's limit; it
+    /// never fails or panics. Only appropriate for bookkeeping that cannot reasonably fail (e.g.
+    /// tracking memory that has already been allocated).
+    fn grow(&self, reservation: &mut MemoryReservation, additional: usize);
+
+    /// Shrinks `reservation` by `shrink` bytes, returning the bytes to the pool.
+    fn shrink(&self, reservation: &mut MemoryReservation, shrink: usize);
+
+    /// The total number of bytes currently reserved across all consumers.
+    fn reserved(&self) -> usize;
+
+    /// Removes `name`'s consumer-level accounting entirely, called once its [`MemoryReservation`]
+    /// is dropped. Pools that track per-consumer fairness (e.g. `FairSpillPool`) must override
+    /// this so a finished consumer stops counting toward that computation; pools with no such
+    /// per-consumer state (e.g. `GreedyMemoryPool`) can leave the no-op default.
+    fn deregister(&self, _name: &str) {}
+}
+
+/// An RAII handle to a consumer's share of a [`MemoryPool`].
+///
+/// The reservation's current size is released back to the owning pool when it is dropped, so
+/// operators can simply hold one for the lifetime of their allocation rather than remembering to
+/// call back into the pool on every exit path.
+pub struct MemoryReservation {
+    name: String,
+    size: usize,
+    pool: Arc<dyn MemoryPool>,
+}
+
+impl MemoryReservation {
+    /// Visible to sibling pool implementations (e.g. `FairSpillPool`) that need to construct a
+    /// reservation directly after doing their own consumer bookkeeping, rather than bouncing back
+    /// through a `MemoryPool::register_consumer` call that would re-run that bookkeeping.
+    pub(super) fn new(name: String, pool: Arc<dyn MemoryPool>) -> Self {
+        Self {
+            name,
+            size: 0,
+            pool,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of bytes currently held by this reservation.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Attempts to grow this reservation by `additional` bytes, returning an error if the
+    /// backing pool cannot accommodate the growth.
+    pub fn try_grow(&mut self, additional: usize) -> Result<()> {
+        let pool = self.pool.clone();
+        pool.try_grow(self, additional)
+    }
+
+    /// Grows this reservation by `additional` bytes unconditionally, even past the pool's limit.
+    pub fn grow(&mut self, additional: usize) {
+        let pool = self.pool.clone();
+        pool.grow(self, additional)
+    }
+
+    /// Shrinks this reservation by `shrink` bytes, returning them to the pool.
+    pub fn shrink(&mut self, shrink: usize) {
+        let pool = self.pool.clone();
+        pool.shrink(self, shrink)
+    }
+
+    /// Only for implementors of [`MemoryPool`] to mutate the tracked size directly.
+    pub(super) fn set_size(&mut self, size: usize) {
+        self.size = size;
+    }
+}
+
+impl fmt::Debug for MemoryReservation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryReservation")
+            .field("name", &self.name)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        if self.size > 0 {
+            let size = self.size;
+            self.pool.clone().shrink(self, size);
+        }
+        // Let the pool drop this consumer's accounting entirely, not just zero its reserved
+        // bytes, so e.g. `FairSpillPool`'s `num_spillable()` reflects currently-active consumers
+        // rather than growing forever with every consumer that has ever registered.
+        self.pool.deregister(&self.name);
+    }
+}
+
+/// The default [`MemoryPool`]: a single shared budget, first come first served.
+///
+/// A grow past `limit` is rejected outright; it is up to the caller to spill or abort. There is
+/// no notion of fairness between consumers here — see [`super::fair_spill_pool::FairSpillPool`]
+/// for a policy that partitions the budget between spillable and unspillable consumers.
+#[derive(Debug)]
+pub struct GreedyMemoryPool {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl GreedyMemoryPool {
+    pub fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            used: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl MemoryPool for GreedyMemoryPool {
+    fn register_consumer(self: Arc<Self>, name: String) -> MemoryReservation {
+        MemoryReservation::new(name, self)
+    }
+
+    fn try_grow(&self, reservation: &mut MemoryReservation, additional: usize) -> Result<()> {
+        self.used
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+                (used + additional <= self.limit).then_some(used + additional)
+            })
+            .map_err(|used| {
+                RwError::from(ErrorCode::InternalError(format!(
+                    "failed to grow memory reservation {:?} by {} bytes: {} of {} already in use",
+                    reservation.name(),
+                    additional,
+                    used,
+                    self.limit
+                )))
+            })?;
+        reservation.set_size(reservation.size() + additional);
+        Ok(())
+    }
+
+    fn grow(&self, reservation: &mut MemoryReservation, additional: usize) {
+        self.used.fetch_add(additional, Ordering::SeqCst);
+        reservation.set_size(reservation.size() + additional);
+    }
+
+    fn shrink(&self, reservation: &mut MemoryReservation, shrink: usize) {
+        self.used.fetch_sub(shrink, Ordering::SeqCst);
+        reservation.set_size(reservation.size() - shrink);
+    }
+
+    fn reserved(&self) -> usize {
+        self.used.load(Ordering::SeqCst)
+    }
+}
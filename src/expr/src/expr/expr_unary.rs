@@ -14,6 +14,11 @@
 
 //! For expression that only accept one value as input (e.g. CAST)
 
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use itertools::Itertools;
+use once_cell::sync::Lazy;
 use risingwave_common::array::*;
 use risingwave_common::buffer::Bitmap;
 use risingwave_common::types::*;
@@ -51,25 +56,6 @@ use crate::{for_all_cast_variants, ExprError, Result};
 /// * $input: child array type
 /// * $rt: The return type in that the operation will calculate
 /// * $func: The scalar function for expression
-macro_rules! gen_unary_impl {
-    ([$expr_name: literal, $child:expr, $ret:expr], $( { $input:ident, $rt: ident, $func:ident },)*) => {
-        match ($child.return_type()) {
-            $(
-                $input! { type_match_pattern } => Box::new(
-                        UnaryExpression::<$input! { type_array}, $rt! {type_array}, _>::new(
-                            $child,
-                            $ret.clone(),
-                            $func,
-                        )
-                ),
-            )*
-            _ => {
-                return Err(ExprError::UnsupportedFunction(format!("{}({:?}) -> {:?}", $expr_name, $child.return_type(), $ret)));
-            }
-        }
-    };
-}
-
 macro_rules! gen_unary_impl_fast {
     ([$expr_name: literal, $child:expr, $ret:expr], $( { $input:ident, $rt: ident, $func:expr },)*) => {
         match ($child.return_type()) {
@@ -83,30 +69,6 @@ macro_rules! gen_unary_impl_fast {
     };
 }
 
-macro_rules! gen_unary_atm_expr  {
-    (
-        $expr_name: literal,
-        $child:expr,
-        $ret:expr,
-        $general_func:ident,
-        {
-            $( { $input:ident, $rt:ident, $func:ident }, )*
-        } $(,)?
-    ) => {
-        gen_unary_impl! {
-            [$expr_name, $child, $ret],
-            { int16, int16, $general_func },
-            { int32, int32, $general_func },
-            { int64, int64, $general_func },
-            { float32, float32, $general_func },
-            { float64, float64, $general_func },
-            $(
-                { $input, $rt, $func },
-            )*
-        }
-    };
-}
-
 macro_rules! gen_round_expr {
     (
         $expr_name:literal,
@@ -123,6 +85,91 @@ macro_rules! gen_round_expr {
     };
 }
 
+/// A class of input types a [`UnarySignature`] will accept, independent of which concrete
+/// implementation ends up bound to the call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TypeClass {
+    /// Only this exact type is a member of the class.
+    Exact(DataType),
+    /// Any of the fixed-width integer or floating-point types, or `Decimal`.
+    Numeric,
+    /// Any of the fixed-width integer types.
+    Integer,
+    /// Either floating-point type.
+    Float,
+    /// Every input type is a member; used for truly polymorphic signatures.
+    Any,
+}
+
+impl TypeClass {
+    fn matches(&self, ty: &DataType) -> bool {
+        match self {
+            TypeClass::Exact(t) => t == ty,
+            TypeClass::Numeric => matches!(
+                ty,
+                DataType::Int16
+                    | DataType::Int32
+                    | DataType::Int64
+                    | DataType::Float32
+                    | DataType::Float64
+                    | DataType::Decimal
+            ),
+            TypeClass::Integer => matches!(ty, DataType::Int16 | DataType::Int32 | DataType::Int64),
+            TypeClass::Float => matches!(ty, DataType::Float32 | DataType::Float64),
+            TypeClass::Any => true,
+        }
+    }
+}
+
+/// How a child whose type is a member of a [`UnarySignature`]'s [`TypeClass`] gets bound to the
+/// concrete implementation the signature stands for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Coercion {
+    /// The child is bound to the implementation as-is, with no cast inserted.
+    Identity,
+    /// The child is cast to this type first, via the existing `Cast` machinery, then bound. Used
+    /// when a signature's implementation is only written for one concrete type (e.g. `f64`) but
+    /// the function should still accept the rest of its `TypeClass` (e.g. any numeric type).
+    ImplicitTo(DataType),
+}
+
+/// One way a unary function can be invoked: the class of input types it accepts, and how a match
+/// gets bound to the concrete implementation.
+struct UnarySignature {
+    accepts: TypeClass,
+    coercion: Coercion,
+}
+
+/// Resolves `child_expr` against `signatures` in order, returning the (possibly re-wrapped in an
+/// implicit cast) child ready to bind to the caller's concrete implementation.
+///
+/// This is the piece that lets e.g. `exp(int_col)` work without the planner pre-inserting a cast:
+/// the caller declares one signature accepting any numeric type, coerced to `Float64`, and this
+/// function inserts that cast automatically when the child isn't already `Float64`.
+fn resolve_unary_signature(
+    expr_name: &str,
+    signatures: &[UnarySignature],
+    child_expr: BoxedExpression,
+) -> Result<BoxedExpression> {
+    let child_type = child_expr.return_type();
+    let Some(sig) = signatures.iter().find(|sig| sig.accepts.matches(&child_type)) else {
+        return Err(ExprError::UnsupportedFunction(format!(
+            "{}({:?}): no signature accepts this input type; candidates: {}",
+            expr_name,
+            child_type,
+            signatures
+                .iter()
+                .map(|sig| format!("{:?}", sig.accepts))
+                .join(", "),
+        )));
+    };
+    match &sig.coercion {
+        Coercion::Identity => Ok(child_expr),
+        Coercion::ImplicitTo(target) if *target == child_type => Ok(child_expr),
+        Coercion::ImplicitTo(target) => new_unary_expr(ProstType::Cast, target.clone(), child_expr),
+    }
+}
+
 /// Create a new unary expression.
 pub fn new_unary_expr(
     expr_type: ProstType,
@@ -200,6 +247,82 @@ pub fn new_unary_expr(
 
             for_all_cast_variants! { gen_cast_impl }
         }
+        (
+            ProstType::TryCast,
+            DataType::List {
+                datatype: target_elem_type,
+            },
+            DataType::Varchar,
+        ) => Box::new(TryCastExpression::<Utf8Array, ListArray, _>::new(
+            child_expr,
+            return_type,
+            move |input| str_to_list(input, &target_elem_type),
+        )),
+        (ProstType::TryCast, DataType::Struct(rty), DataType::Struct(lty)) => {
+            Box::new(TryCastExpression::<StructArray, StructArray, _>::new(
+                child_expr,
+                return_type,
+                move |input| struct_cast(input, &lty, &rty),
+            ))
+        }
+        (
+            ProstType::TryCast,
+            DataType::List {
+                datatype: target_elem_type,
+            },
+            DataType::List {
+                datatype: source_elem_type,
+            },
+        ) => Box::new(TryCastExpression::<ListArray, ListArray, _>::new(
+            child_expr,
+            return_type,
+            move |input| list_cast(input, &source_elem_type, &target_elem_type),
+        )),
+        (ProstType::TryCast, _, _) => {
+            // Mirrors the `Cast` arm above's `gen_cast_impl!`/`for_all_cast_variants!` dispatch,
+            // but a fallible (non-infallible, non-`varchar`-output) conversion binds to
+            // `TryCastExpression` instead of `UnaryExpression`, so a per-row failure writes NULL
+            // rather than aborting the whole chunk's evaluation. The `str_to_list`/`struct_cast`/
+            // `list_cast` conversions are handled by the three arms above this one, matching how
+            // the `Cast` arm special-cases them ahead of its own catch-all.
+            macro_rules! gen_try_cast_impl {
+                ($( { $input:ident, $cast:ident, $func:expr, $infallible:ident } ),*) => {
+                    match (child_expr.return_type(), return_type.clone()) {
+                        $(
+                            ($input! { type_match_pattern }, $cast! { type_match_pattern }) => gen_try_cast_impl!(arm: $input, $cast, $func, $infallible),
+                        )*
+                        _ => {
+                            return Err(ExprError::UnsupportedCast(child_expr.return_type(), return_type));
+                        }
+                    }
+                };
+                // Stringifying a value never fails, so `TRY_CAST` to `varchar` behaves like `CAST`.
+                (arm: $input:ident, varchar, $func:expr, false) => {
+                    UnaryBytesExpression::< $input! { type_array }, _>::new(
+                        child_expr,
+                        return_type.clone(),
+                        $func
+                    ).boxed()
+                };
+                // An infallible conversion can't produce the NULL-on-error case either.
+                (arm: $input:ident, $cast:ident, $func:expr, true) => {
+                    template_fast::UnaryExpression::new(
+                        child_expr,
+                        return_type.clone(),
+                        $func
+                    ).boxed()
+                };
+                (arm: $input:ident, $cast:ident, $func:expr, false) => {
+                    TryCastExpression::< $input! { type_array }, $cast! { type_array }, _>::new(
+                        child_expr,
+                        return_type.clone(),
+                        $func
+                    ).boxed()
+                };
+            }
+
+            for_all_cast_variants! { gen_try_cast_impl }
+        }
         (ProstType::BoolOut, _, DataType::Boolean) => Box::new(
             UnaryBytesExpression::<BoolArray, _>::new(child_expr, return_type, bool_out),
         ),
@@ -266,18 +389,39 @@ pub fn new_unary_expr(
             bit_length,
         )),
         (ProstType::Neg, _, _) => {
-            gen_unary_atm_expr! { "Neg", child_expr, return_type, general_neg,
-                {
-                    { decimal, decimal, general_neg },
-                }
-            }
+            // Unlike `Exp`/`ToTimestamp` below, `Neg` preserves its input's own concrete type
+            // rather than coercing every accepted type to one (negating an `int32` should stay
+            // `int32`, not become `float64`), so its signature binds with `Coercion::Identity`
+            // and dispatches on the resolved type at the `ScalarRefImpl` level in `neg_datum`
+            // instead of a macro-generated `UnaryExpression<Array, Array, _>` per type.
+            let child_expr = resolve_unary_signature(
+                "Neg",
+                &[UnarySignature {
+                    accepts: TypeClass::Numeric,
+                    coercion: Coercion::Identity,
+                }],
+                child_expr,
+            )?;
+            Box::new(DatumUnaryExpression {
+                child: child_expr,
+                return_type,
+                func: neg_datum,
+            })
         }
         (ProstType::Abs, _, _) => {
-            gen_unary_atm_expr! { "Abs", child_expr, return_type, general_abs,
-                {
-                    {decimal, decimal, decimal_abs},
-                }
-            }
+            let child_expr = resolve_unary_signature(
+                "Abs",
+                &[UnarySignature {
+                    accepts: TypeClass::Numeric,
+                    coercion: Coercion::Identity,
+                }],
+                child_expr,
+            )?;
+            Box::new(DatumUnaryExpression {
+                child: child_expr,
+                return_type,
+                func: abs_datum,
+            })
         }
         (ProstType::BitwiseNot, _, _) => {
             gen_unary_impl_fast! {
@@ -294,14 +438,53 @@ pub fn new_unary_expr(
             gen_round_expr! {"Floor", child_expr, return_type, floor_f64, floor_decimal}
         }
         (ProstType::Round, _, _) => {
-            gen_round_expr! {"Ceil", child_expr, return_type, round_f64, round_decimal}
+            // `Round` only ever accepts the two types `round_f64`/`round_decimal` are written
+            // for, unlike `Neg`/`Abs`'s full `TypeClass::Numeric` — each gets its own `Exact`
+            // signature rather than broadening acceptance to integers.
+            let child_expr = resolve_unary_signature(
+                "Round",
+                &[
+                    UnarySignature {
+                        accepts: TypeClass::Exact(DataType::Float64),
+                        coercion: Coercion::Identity,
+                    },
+                    UnarySignature {
+                        accepts: TypeClass::Exact(DataType::Decimal),
+                        coercion: Coercion::Identity,
+                    },
+                ],
+                child_expr,
+            )?;
+            Box::new(DatumUnaryExpression {
+                child: child_expr,
+                return_type,
+                func: round_datum,
+            })
         }
-        (ProstType::Exp, _, _) => Box::new(UnaryExpression::<F64Array, F64Array, _>::new(
-            child_expr,
-            return_type,
-            exp_f64,
-        )),
-        (ProstType::ToTimestamp, DataType::Timestamptz, DataType::Float64) => {
+        (ProstType::Exp, _, _) => {
+            let child_expr = resolve_unary_signature(
+                "Exp",
+                &[UnarySignature {
+                    accepts: TypeClass::Numeric,
+                    coercion: Coercion::ImplicitTo(DataType::Float64),
+                }],
+                child_expr,
+            )?;
+            Box::new(UnaryExpression::<F64Array, F64Array, _>::new(
+                child_expr,
+                return_type,
+                exp_f64,
+            ))
+        }
+        (ProstType::ToTimestamp, DataType::Timestamptz, _) => {
+            let child_expr = resolve_unary_signature(
+                "ToTimestamp",
+                &[UnarySignature {
+                    accepts: TypeClass::Numeric,
+                    coercion: Coercion::ImplicitTo(DataType::Float64),
+                }],
+                child_expr,
+            )?;
             Box::new(UnaryExpression::<F64Array, I64Array, _>::new(
                 child_expr,
                 return_type,
@@ -321,16 +504,167 @@ pub fn new_unary_expr(
             .boxed()
         }
         (expr, ret, child) => {
-            return Err(ExprError::UnsupportedFunction(format!(
-                "{:?}({:?}) -> {:?}",
-                expr, child, ret
-            )));
+            // Not one of the built-ins above: fall through to the UDF registry, keyed by this
+            // variant's own name, before giving up. This is how a `ProstType` variant that isn't
+            // (yet) one of the hand-written arms above can still resolve to a registered scalar
+            // function, e.g. `register_unary_udf("Sin", ...)` backing a future `ProstType::Sin`.
+            let name = format!("{:?}", expr);
+            match new_unary_udf_expr(&name, return_type.clone(), child_expr) {
+                Ok(udf_expr) => udf_expr,
+                Err(_) => {
+                    return Err(ExprError::UnsupportedFunction(format!(
+                        "{:?}({:?}) -> {:?}",
+                        expr, child, ret
+                    )));
+                }
+            }
         }
     };
 
     Ok(expr)
 }
 
+/// Like [`UnaryExpression`], but for `TRY_CAST`: a conversion failure on a given row writes NULL
+/// into that row's output instead of propagating as an [`ExprError`] and aborting the rest of the
+/// chunk.
+#[derive(Debug)]
+struct TryCastExpression<Input: Array, Output: Array, F> {
+    child: BoxedExpression,
+    return_type: DataType,
+    func: F,
+    _phantom: std::marker::PhantomData<(Input, Output)>,
+}
+
+impl<Input, Output, F> TryCastExpression<Input, Output, F>
+where
+    Input: Array,
+    Output: Array,
+    F: Fn(Input::RefItem<'_>) -> Result<Output::OwnedItem> + Sync + Send,
+{
+    fn new(child: BoxedExpression, return_type: DataType, func: F) -> Self {
+        Self {
+            child,
+            return_type,
+            func,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<Input, Output, F> Expression for TryCastExpression<Input, Output, F>
+where
+    Input: Array,
+    Output: Array,
+    F: Fn(Input::RefItem<'_>) -> Result<Output::OwnedItem> + Sync + Send + std::fmt::Debug,
+{
+    fn return_type(&self) -> DataType {
+        self.return_type.clone()
+    }
+
+    fn eval(&self, input: &DataChunk) -> Result<ArrayRef> {
+        let child_result = self.child.eval(input)?;
+        let arr: &Input = child_result.as_ref().into();
+        let values: Vec<Option<Output::OwnedItem>> = arr
+            .iter()
+            .map(|item| item.and_then(|scalar| (self.func)(scalar).ok()))
+            .collect();
+        Ok(Arc::new(ArrayImpl::from(Output::from_iter(&values))))
+    }
+
+    fn eval_row(&self, input: &OwnedRow) -> Result<Datum> {
+        let datum = self.child.eval_row(input)?;
+        let result = datum
+            .and_then(|scalar| Input::RefItem::try_from(scalar.as_scalar_ref_impl()).ok())
+            .and_then(|scalar| (self.func)(scalar).ok())
+            .map(|value| value.to_scalar_value());
+        Ok(result)
+    }
+}
+
+/// Executes a unary scalar function at the [`ScalarRefImpl`]/[`Datum`] level rather than over one
+/// concrete [`Array`] pair. For a [`UnarySignature`] list resolved with [`Coercion::Identity`],
+/// the function itself branches on the resolved input's concrete type (see [`neg_datum`]), so
+/// binding it here accepts every type in the signature's [`TypeClass`] without a macro-generated
+/// `UnaryExpression<Array, Array, _>` match arm per type.
+#[derive(Debug)]
+struct DatumUnaryExpression {
+    child: BoxedExpression,
+    return_type: DataType,
+    func: fn(Option<ScalarRefImpl<'_>>) -> Result<Datum>,
+}
+
+impl Expression for DatumUnaryExpression {
+    fn return_type(&self) -> DataType {
+        self.return_type.clone()
+    }
+
+    fn eval(&self, input: &DataChunk) -> Result<ArrayRef> {
+        let child_result = self.child.eval(input)?;
+        let mut builder = ArrayBuilderImpl::with_type(child_result.len(), self.return_type.clone());
+        for datum_ref in child_result.iter() {
+            let datum = (self.func)(datum_ref)?;
+            builder.append_datum(&datum);
+        }
+        Ok(Arc::new(builder.finish()))
+    }
+
+    fn eval_row(&self, input: &OwnedRow) -> Result<Datum> {
+        let datum = self.child.eval_row(input)?;
+        (self.func)(datum.as_ref().map(|s| s.as_scalar_ref_impl()))
+    }
+}
+
+/// `NEG`'s `ScalarRefImpl` dispatch: `general_neg` is generic over every member of
+/// `TypeClass::Numeric`, so this just routes each variant to its own monomorphization and
+/// reboxes the result, rather than a per-type `UnaryExpression`.
+fn neg_datum(value: Option<ScalarRefImpl<'_>>) -> Result<Datum> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let result = match value {
+        ScalarRefImpl::Int16(v) => ScalarImpl::Int16(general_neg(v)),
+        ScalarRefImpl::Int32(v) => ScalarImpl::Int32(general_neg(v)),
+        ScalarRefImpl::Int64(v) => ScalarImpl::Int64(general_neg(v)),
+        ScalarRefImpl::Float32(v) => ScalarImpl::Float32(general_neg(v)),
+        ScalarRefImpl::Float64(v) => ScalarImpl::Float64(general_neg(v)),
+        ScalarRefImpl::Decimal(v) => ScalarImpl::Decimal(general_neg(v)),
+        _ => unreachable!("Neg's signature only accepts TypeClass::Numeric"),
+    };
+    Ok(Some(result))
+}
+
+/// `ABS`'s `ScalarRefImpl` dispatch: decimal uses its own `decimal_abs`, same as the old
+/// macro-generated arms did, while every other numeric type shares the generic `general_abs`.
+fn abs_datum(value: Option<ScalarRefImpl<'_>>) -> Result<Datum> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let result = match value {
+        ScalarRefImpl::Int16(v) => ScalarImpl::Int16(general_abs(v)),
+        ScalarRefImpl::Int32(v) => ScalarImpl::Int32(general_abs(v)),
+        ScalarRefImpl::Int64(v) => ScalarImpl::Int64(general_abs(v)),
+        ScalarRefImpl::Float32(v) => ScalarImpl::Float32(general_abs(v)),
+        ScalarRefImpl::Float64(v) => ScalarImpl::Float64(general_abs(v)),
+        ScalarRefImpl::Decimal(v) => ScalarImpl::Decimal(decimal_abs(v)),
+        _ => unreachable!("Abs's signature only accepts TypeClass::Numeric"),
+    };
+    Ok(Some(result))
+}
+
+/// `ROUND`'s `ScalarRefImpl` dispatch, covering exactly the two types its signature accepts
+/// (`Float64`/`Decimal`) — unlike `Neg`/`Abs`, it was never defined over the integer types.
+fn round_datum(value: Option<ScalarRefImpl<'_>>) -> Result<Datum> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let result = match value {
+        ScalarRefImpl::Float64(v) => ScalarImpl::Float64(round_f64(v)),
+        ScalarRefImpl::Decimal(v) => ScalarImpl::Decimal(round_decimal(v)),
+        _ => unreachable!("Round's signature only accepts Float64 or Decimal"),
+    };
+    Ok(Some(result))
+}
+
 pub fn new_length_default(expr_ia1: BoxedExpression, return_type: DataType) -> BoxedExpression {
     Box::new(UnaryExpression::<Utf8Array, I32Array, _>::new(
         expr_ia1,
@@ -363,11 +697,118 @@ pub fn new_rtrim_expr(expr_ia1: BoxedExpression, return_type: DataType) -> Boxed
     ))
 }
 
+/// A named scalar function registered for unary expressions, bound by [`DataType`] rather than by
+/// a concrete [`Array`] pair, so it can be looked up and attached at expression-build time without
+/// the caller needing to know its element types.
+#[derive(Clone)]
+pub struct UnaryUdf {
+    pub input_type: DataType,
+    pub output_type: DataType,
+    func: Arc<dyn for<'a> Fn(Option<ScalarRefImpl<'a>>) -> Result<Datum> + Send + Sync>,
+}
+
+impl std::fmt::Debug for UnaryUdf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnaryUdf")
+            .field("input_type", &self.input_type)
+            .field("output_type", &self.output_type)
+            .finish()
+    }
+}
+
+impl UnaryUdf {
+    pub fn new(
+        input_type: DataType,
+        output_type: DataType,
+        func: impl for<'a> Fn(Option<ScalarRefImpl<'a>>) -> Result<Datum> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            input_type,
+            output_type,
+            func: Arc::new(func),
+        }
+    }
+}
+
+/// Process-global registry of [`UnaryUdf`]s, keyed by the name a planner would reference them by.
+/// Separate from the built-in [`ProstType`] dispatch in [`new_unary_expr`], since a UDF reference
+/// carries a name rather than one of the fixed protobuf enum variants.
+static UNARY_UDF_REGISTRY: Lazy<RwLock<HashMap<String, UnaryUdf>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Registers `udf` under `name`, replacing any existing registration of the same name.
+pub fn register_unary_udf(name: impl Into<String>, udf: UnaryUdf) {
+    UNARY_UDF_REGISTRY.write().unwrap().insert(name.into(), udf);
+}
+
+fn lookup_unary_udf(name: &str) -> Option<UnaryUdf> {
+    UNARY_UDF_REGISTRY.read().unwrap().get(name).cloned()
+}
+
+/// Binds the [`UnaryUdf`] registered under `name` to `child_expr`.
+///
+/// Intended as the fallthrough a caller reaches after `expr_type` in an `ExprNode` doesn't match
+/// any of the built-in [`ProstType`] variants [`new_unary_expr`] handles: rather than failing
+/// outright, the caller resolves the call's function name against the UDF registry and binds it
+/// here instead. Returns [`ExprError::UnsupportedFunction`] if no UDF is registered under `name`,
+/// or if the registered `input_type` doesn't match what `child_expr` actually produces.
+pub fn new_unary_udf_expr(
+    name: &str,
+    return_type: DataType,
+    child_expr: BoxedExpression,
+) -> Result<BoxedExpression> {
+    let udf = lookup_unary_udf(name)
+        .ok_or_else(|| ExprError::UnsupportedFunction(format!("no scalar UDF named {:?}", name)))?;
+    let child_type = child_expr.return_type();
+    if udf.input_type != child_type {
+        return Err(ExprError::UnsupportedFunction(format!(
+            "UDF {:?} expects input type {:?}, got {:?}",
+            name, udf.input_type, child_type
+        )));
+    }
+    Ok(Box::new(UnaryUdfExpression {
+        child: child_expr,
+        return_type,
+        udf,
+    }))
+}
+
+/// Executor for a call bound to a [`UnaryUdf`]. Operates at the [`ScalarRefImpl`]/[`Datum`] level
+/// rather than over a concrete [`Array`] pair, since the UDF's closure is type-erased by the
+/// [`DataType`]s it was registered with.
+#[derive(Debug)]
+struct UnaryUdfExpression {
+    child: BoxedExpression,
+    return_type: DataType,
+    udf: UnaryUdf,
+}
+
+impl Expression for UnaryUdfExpression {
+    fn return_type(&self) -> DataType {
+        self.return_type.clone()
+    }
+
+    fn eval(&self, input: &DataChunk) -> Result<ArrayRef> {
+        let child_result = self.child.eval(input)?;
+        let mut builder = ArrayBuilderImpl::with_type(child_result.len(), self.return_type.clone());
+        for datum_ref in child_result.iter() {
+            let datum = (self.udf.func)(datum_ref)?;
+            builder.append_datum(&datum);
+        }
+        Ok(Arc::new(builder.finish()))
+    }
+
+    fn eval_row(&self, input: &OwnedRow) -> Result<Datum> {
+        let datum = self.child.eval_row(input)?;
+        (self.udf.func)(datum.as_ref().map(|s| s.as_scalar_ref_impl()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
     use risingwave_common::array::*;
-    use risingwave_common::types::{NaiveDateWrapper, Scalar};
+    use risingwave_common::types::{NaiveDateWrapper, Scalar, ScalarImpl};
     use risingwave_pb::data::data_type::TypeName;
     use risingwave_pb::data::DataType;
     use risingwave_pb::expr::expr_node::{RexNode, Type};
@@ -384,6 +825,33 @@ mod tests {
         test_str_to_int16::<I16Array, _>(|x| str_parse(x).unwrap());
     }
 
+    #[test]
+    fn test_exp_implicit_cast_from_int() {
+        let col1 = I32Array::from_iter(&[Some(0), Some(1), Some(2)]).into();
+        let data_chunk = DataChunk::new(vec![col1], 3);
+        let return_type = DataType {
+            type_name: TypeName::Float64 as i32,
+            is_nullable: false,
+            ..Default::default()
+        };
+        let expr = ExprNode {
+            expr_type: Type::Exp as i32,
+            return_type: Some(return_type),
+            rex_node: Some(RexNode::FuncCall(FunctionCall {
+                children: vec![make_input_ref(0, TypeName::Int32)],
+            })),
+        };
+        // `Exp` is only ever implemented for `f64`; resolving against an `Int32` child should
+        // insert an implicit cast rather than failing with `UnsupportedFunction`.
+        let vec_executor = build_from_prost(&expr).unwrap();
+        let res = vec_executor.eval(&data_chunk).unwrap();
+        let arr: &F64Array = res.as_ref().into();
+        for (idx, item) in arr.iter().enumerate() {
+            let expected = (idx as f64).exp();
+            assert!((item.unwrap().0 - expected).abs() < 1e-9);
+        }
+    }
+
     #[test]
     fn test_i16_to_i32() {
         let mut input = Vec::<Option<i16>>::new();
@@ -427,6 +895,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_unary_udf_registry_dispatch() {
+        register_unary_udf(
+            "test_double_i32",
+            UnaryUdf::new(DataType::Int32, DataType::Int32, |datum| {
+                Ok(datum.map(|scalar| ScalarImpl::from(scalar.into_int32() * 2)))
+            }),
+        );
+
+        let child = build_from_prost(&make_input_ref(0, TypeName::Int32)).unwrap();
+        let expr = new_unary_udf_expr("test_double_i32", DataType::Int32, child).unwrap();
+
+        let col1 = I32Array::from_iter(&[Some(3), None, Some(-4)]).into();
+        let data_chunk = DataChunk::new(vec![col1], 3);
+        let res = expr.eval(&data_chunk).unwrap();
+        let arr: &I32Array = res.as_ref().into();
+        let expected = [Some(6), None, Some(-8)];
+        for (idx, item) in arr.iter().enumerate() {
+            assert_eq!(item, expected[idx].as_ref().map(|x| x.as_scalar_ref()));
+        }
+    }
+
+    #[test]
+    fn test_unary_udf_registry_rejects_unregistered_name() {
+        let child = build_from_prost(&make_input_ref(0, TypeName::Int32)).unwrap();
+        assert!(new_unary_udf_expr("no_such_udf", DataType::Int32, child).is_err());
+    }
+
     #[test]
     fn test_neg() {
         let mut input = Vec::<Option<i32>>::new();
@@ -470,6 +966,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_cast_str_to_int32_nulls_on_parse_failure() {
+        let input: Vec<Option<&str>> = vec![Some("42"), Some("not-a-number"), None, Some("7")];
+        let col1 = Utf8Array::from_iter(&input).into();
+        let data_chunk = DataChunk::new(vec![col1], input.len());
+        let return_type = DataType {
+            type_name: TypeName::Int32 as i32,
+            is_nullable: true,
+            ..Default::default()
+        };
+        let expr = ExprNode {
+            expr_type: Type::TryCast as i32,
+            return_type: Some(return_type),
+            rex_node: Some(RexNode::FuncCall(FunctionCall {
+                children: vec![make_input_ref(0, TypeName::Varchar)],
+            })),
+        };
+        let vec_executor = build_from_prost(&expr).unwrap();
+        let res = vec_executor.eval(&data_chunk).unwrap();
+        let arr: &I32Array = res.as_ref().into();
+        // An unparseable row becomes NULL instead of aborting the whole chunk's evaluation.
+        let expected = [Some(42), None, None, Some(7)];
+        for (idx, item) in arr.iter().enumerate() {
+            assert_eq!(item, expected[idx].as_ref().map(|x| x.as_scalar_ref()));
+        }
+    }
+
     fn test_str_to_int16<A, F>(f: F)
     where
         A: Array,
@@ -15,31 +15,130 @@
 use std::fmt;
 
 use fixedbitset::FixedBitSet;
+use risingwave_common::types::OrderedFloat;
 use risingwave_pb::stream_plan::stream_node::NodeBody as ProstStreamNode;
 
 use super::{ExprRewritable, LogicalTopN, PlanBase, PlanRef, PlanTreeNodeUnary, StreamNode};
-use crate::optimizer::property::{Distribution, Order};
+use crate::optimizer::property::{Distribution, Order, RequiredDist};
 use crate::stream_fragmenter::BuildFragmentGraphState;
 
+/// The kind of cap a [`StreamTopN`] enforces: a constant row count (`LIMIT n`) or a fraction of
+/// the current group's cardinality (`LIMIT n PERCENT`), whose effective row count is recomputed
+/// by the executor as rows arrive. Percent limits pair naturally with [`StreamTopN::with_ties`],
+/// since "the top 10%" is usually expected to include ties at the cut line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    Count(u64),
+    Percent(OrderedFloat<f64>),
+}
+
+/// Distinguishes the role a [`StreamTopN`] node plays in the plan.
+///
+/// A `Single` node is the whole story: its input is already on a single distribution, so one
+/// heap sees every row. A sharded input instead goes through a `Local`/`Global` pair: `Local`
+/// keeps only `offset + limit` (plus tie peers) candidates per upstream partition, and `Global`
+/// re-ranks the merged, bounded candidate set behind a `Single`-distribution exchange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TopNPhase {
+    Single,
+    Local,
+    Global,
+}
+
 /// `StreamTopN` implements [`super::LogicalTopN`] to find the top N elements with a heap
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StreamTopN {
     pub base: PlanBase,
     logical: LogicalTopN,
+    phase: TopNPhase,
+    limit_kind: LimitKind,
 }
 
 impl StreamTopN {
     pub fn new(logical: LogicalTopN) -> Self {
-        assert!(logical.group_key().is_empty());
         assert!(logical.limit() > 0);
+        Self::with_limit_kind(logical.clone(), LimitKind::Count(logical.limit()))
+    }
+
+    /// Builds a TopN capped by a fraction of the current group's cardinality (`LIMIT n PERCENT`)
+    /// rather than a constant row count.
+    ///
+    /// Unlike the constant-count case, a percent limit's effective row count depends on the
+    /// *current* cardinality of the whole group, which a `Local` partial stage cannot know in
+    /// isolation: two partitions holding different share counts would compute different cutoffs
+    /// against the same percentage. So percent-limit TopN always stays single-stage for now,
+    /// rather than going through [`Self::two_phase`].
+    pub fn with_percent_limit(logical: LogicalTopN, percent: OrderedFloat<f64>) -> Self {
+        assert!(*percent > 0.0 && *percent <= 100.0);
+        assert!(logical.group_key().is_empty());
+        Self::with_limit_kind(logical, LimitKind::Percent(percent))
+    }
+
+    fn with_limit_kind(logical: LogicalTopN, limit_kind: LimitKind) -> Self {
+        assert!(logical.group_key().is_empty());
+        match (logical.input().distribution(), limit_kind) {
+            (Distribution::Single, _) => Self::new_inner(logical, TopNPhase::Single, limit_kind),
+            (
+                Distribution::HashShard(_) | Distribution::UpstreamHashShard(_, _),
+                LimitKind::Count(_),
+            ) => Self::two_phase(logical, limit_kind),
+            // See `with_percent_limit`: a percent limit can't be split into local partials, so
+            // fall back to a single-stage plan even over a sharded input — but a `Single`-phase
+            // node must actually run on a single-distribution input, so insert the same exchange
+            // `two_phase` does rather than silently re-ranking each shard independently.
+            (
+                Distribution::HashShard(_) | Distribution::UpstreamHashShard(_, _),
+                LimitKind::Percent(_),
+            ) => {
+                let exchanged = RequiredDist::single()
+                    .enforce_if_not_satisfies(logical.input(), logical.topn_order())
+                    .expect(
+                        "a single-distribution exchange can always be inserted above a stream node",
+                    );
+                Self::new_inner(
+                    logical.clone_with_input(exchanged),
+                    TopNPhase::Single,
+                    limit_kind,
+                )
+            }
+            _ => panic!("StreamTopN requires a `Single` or sharded input distribution"),
+        }
+    }
+
+    /// Splits a TopN over a sharded input into a `Local` partial stage (preserving
+    /// [`Self::topn_order`]) feeding a `Single`-distribution exchange into a `Global` merge
+    /// stage. Each local node only needs to retain `offset + limit` rows, so the merge input is
+    /// bounded by `num_partitions * (offset + limit)` regardless of how large the sharded input
+    /// is.
+    fn two_phase(logical: LogicalTopN, limit_kind: LimitKind) -> Self {
+        let local = Self::new_inner(logical.clone(), TopNPhase::Local, limit_kind);
+        let exchanged = RequiredDist::single()
+            .enforce_if_not_satisfies(local.into(), logical.topn_order())
+            .expect("a single-distribution exchange can always be inserted above a stream node");
+        Self::new_inner(
+            logical.clone_with_input(exchanged),
+            TopNPhase::Global,
+            limit_kind,
+        )
+    }
+
+    fn new_inner(logical: LogicalTopN, phase: TopNPhase, limit_kind: LimitKind) -> Self {
         let ctx = logical.base.ctx.clone();
         let input = logical.input();
         let schema = input.schema().clone();
-        let dist = match logical.input().distribution() {
-            Distribution::Single => Distribution::Single,
-            _ => panic!(),
+        let dist = match phase {
+            TopNPhase::Local => input.distribution().clone(),
+            TopNPhase::Single | TopNPhase::Global => Distribution::Single,
         };
-        let watermark_columns = FixedBitSet::with_capacity(schema.len());
+        // Forward progress on the leading `ORDER BY` column when the input already watermarks
+        // it, so an append-time/event-time TopN doesn't become an unbounded-state operator just
+        // because it sits downstream of a watermark source.
+        let mut watermark_columns = FixedBitSet::with_capacity(schema.len());
+        if let Some(leading) = logical.topn_order().column_orders.first()
+            && input.watermark_columns().contains(leading.column_index)
+        {
+            watermark_columns.insert(leading.column_index);
+        }
 
         let base = PlanBase::new_stream(
             ctx,
@@ -50,11 +149,28 @@ impl StreamTopN {
             false,
             watermark_columns,
         );
-        StreamTopN { base, logical }
+        StreamTopN {
+            base,
+            logical,
+            phase,
+            limit_kind,
+        }
+    }
+
+    pub fn limit_kind(&self) -> LimitKind {
+        self.limit_kind
     }
 
+    /// The constant row count to retain, when this node uses [`LimitKind::Count`]. Percent-limit
+    /// nodes have no fixed count: for them this returns the logical plan's literal `limit`, which
+    /// `to_stream_prost_body` still serializes but the executor ignores in favour of
+    /// `limit_percent`. The two-phase `Local` stage math only runs on `Count` nodes, since
+    /// [`Self::with_percent_limit`] never splits.
     pub fn limit(&self) -> u64 {
-        self.logical.limit()
+        match self.limit_kind {
+            LimitKind::Count(limit) => limit,
+            LimitKind::Percent(_) => self.logical.limit(),
+        }
     }
 
     pub fn offset(&self) -> u64 {
@@ -68,15 +184,34 @@ impl StreamTopN {
     pub fn topn_order(&self) -> &Order {
         self.logical.topn_order()
     }
+
+    /// The index of the leading `topn_order` column, when it is watermarked, i.e. the column the
+    /// executor can use to evict state rows whose ordering key has fallen permanently below the
+    /// current watermark. `None` means the ordering isn't watermark-backed and the operator's
+    /// state table grows unbounded.
+    pub fn watermark_order_col_idx(&self) -> Option<usize> {
+        let leading = self.topn_order().column_orders.first()?.column_index;
+        self.base
+            .watermark_columns()
+            .contains(leading)
+            .then_some(leading)
+    }
 }
 
 impl fmt::Display for StreamTopN {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.input().append_only() {
-            self.logical.fmt_with_name(f, "StreamAppendOnlyTopN")
-        } else {
-            self.logical.fmt_with_name(f, "StreamTopN")
-        }
+        let percent = matches!(self.limit_kind, LimitKind::Percent(_));
+        let name = match (self.phase, self.input().append_only(), percent) {
+            (TopNPhase::Single, false, false) => "StreamTopN",
+            (TopNPhase::Single, false, true) => "StreamTopNPercent",
+            (TopNPhase::Single, true, false) => "StreamAppendOnlyTopN",
+            (TopNPhase::Single, true, true) => "StreamAppendOnlyTopNPercent",
+            (TopNPhase::Local, false, _) => "StreamLocalTopN",
+            (TopNPhase::Local, true, _) => "StreamAppendOnlyLocalTopN",
+            (TopNPhase::Global, false, _) => "StreamGlobalTopN",
+            (TopNPhase::Global, true, _) => "StreamAppendOnlyGlobalTopN",
+        };
+        self.logical.fmt_with_name(f, name)
     }
 }
 
@@ -86,7 +221,20 @@ impl PlanTreeNodeUnary for StreamTopN {
     }
 
     fn clone_with_input(&self, input: PlanRef) -> Self {
-        Self::new(self.logical.clone_with_input(input))
+        match self.phase {
+            // Re-running `with_limit_kind` on a `Single` node re-derives whether a split is now
+            // possible (percent limits never split, see `with_percent_limit`).
+            TopNPhase::Single => {
+                Self::with_limit_kind(self.logical.clone_with_input(input), self.limit_kind)
+            }
+            // A `Local`/`Global` node keeps its role: re-deriving it would try to split the
+            // already-local or already-global stage again.
+            TopNPhase::Local | TopNPhase::Global => Self::new_inner(
+                self.logical.clone_with_input(input),
+                self.phase,
+                self.limit_kind,
+            ),
+        }
     }
 }
 
@@ -95,9 +243,16 @@ impl_plan_tree_node_for_unary! { StreamTopN }
 impl StreamNode for StreamTopN {
     fn to_stream_prost_body(&self, state: &mut BuildFragmentGraphState) -> ProstStreamNode {
         use risingwave_pb::stream_plan::*;
+        // The `Local` stage must retain `offset + limit` rows itself and defer applying the
+        // offset to the `Global` stage; otherwise a row that should survive the merge could be
+        // dropped locally before the global heap ever sees it.
+        let (limit, offset) = match self.phase {
+            TopNPhase::Local => (self.offset() + self.limit(), 0),
+            TopNPhase::Single | TopNPhase::Global => (self.limit(), self.offset()),
+        };
         let topn_node = TopNNode {
-            limit: self.limit(),
-            offset: self.offset(),
+            limit,
+            offset,
             with_ties: self.with_ties(),
             table: Some(
                 self.logical
@@ -106,6 +261,19 @@ impl StreamNode for StreamTopN {
                     .to_internal_table_prost(),
             ),
             order_by: self.topn_order().to_protobuf(),
+            // Lets the executor size its internal state table: a `Local` node only ever holds
+            // `offset + limit` rows per partition, while `Single`/`Global` hold the final result.
+            is_partial: matches!(self.phase, TopNPhase::Local),
+            // Lets the executor evict state rows that have fallen permanently behind the
+            // watermark, rather than retaining every row it has ever seen.
+            watermark_order_col_idx: self.watermark_order_col_idx().map(|idx| idx as u32),
+            // When set, the executor recomputes the effective row count as `percent` of the
+            // current group cardinality on every update, instead of using the fixed `limit`
+            // above (which is left at the logical plan's literal `limit` and ignored).
+            limit_percent: match self.limit_kind {
+                LimitKind::Count(_) => None,
+                LimitKind::Percent(percent) => Some(*percent),
+            },
         };
         if self.input().append_only() {
             ProstStreamNode::AppendOnlyTopN(topn_node)
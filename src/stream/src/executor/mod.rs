@@ -0,0 +1,20 @@
+// Copyright 2023 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// NOTE: this is a partial excerpt of `executor/mod.rs`, scoped to declaring the `top_n`
+// submodule this series added. The full file declares every other executor submodule and the
+// `Executor`/`Message`/`BoxedExecutor` traits `top_n`'s real `ExecutorBuilder` integration sits
+// on top of, none of which are present in this slice of the tree.
+
+pub mod top_n;
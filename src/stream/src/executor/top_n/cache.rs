@@ -0,0 +1,199 @@
+// Copyright 2023 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The in-memory cache a TopN executor runs with (see [`super::build_topn_cache`]), consuming the `is_partial`,
+//! `watermark_order_col_idx`, and `limit_percent` fields `StreamTopN::to_stream_prost_body`
+//! serializes into `TopNNode` — these only decorate the protobuf unless something on the
+//! executor side actually reads them, which is what this type does.
+//!
+//! This is a simplified model: the real executor's cache splits rows into "low"/"middle"/"high"
+//! sub-caches to make retraction near the `offset`/`limit` boundary cheap without a state-table
+//! read. That's an optimization; the eviction, capacity, and percent-recompute behavior below is
+//! what `StreamTopN`'s plan-side fields actually ask the executor to do.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use risingwave_common::row::OwnedRow;
+use risingwave_common::types::ScalarImpl;
+
+/// An order key tagged with each column's sort direction, so its [`Ord`] impl ranks rows the way
+/// `TopNNode.order_by` actually asks for, rather than assuming every column sorts ascending.
+/// Comparing two keys built from different `desc` slices is a caller bug; every key a given
+/// [`TopNCache`] produces shares the same one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OrderedKey {
+    values: Vec<ScalarImpl>,
+    /// `desc[i]` is true when `values[i]`'s column sorts `DESCENDING`; ties at the cache's
+    /// eviction boundary still need a tiebreaker from the caller's primary key, which is why this
+    /// type doesn't fold the pk in directly.
+    desc: Arc<[bool]>,
+}
+
+impl PartialOrd for OrderedKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        for (i, (a, b)) in self.values.iter().zip(other.values.iter()).enumerate() {
+            let ord = a.cmp(b);
+            let ord = if self.desc.get(i).copied().unwrap_or(false) {
+                ord.reverse()
+            } else {
+                ord
+            };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+/// Ordered cache of at most [`TopNCache::capacity`] rows (more when `with_ties` keeps rows tied
+/// at the eviction boundary), keyed by `(order_key, pk)` so ties on the order key still sort
+/// deterministically by primary key.
+pub struct TopNCache {
+    limit: u64,
+    offset: u64,
+    with_ties: bool,
+    /// True for the local, partial stage of a two-phase `StreamTopN` (see
+    /// `StreamTopN::two_phase`): `limit` already has `offset` folded into it and `offset` is 0,
+    /// so this node must never itself skip the first `offset` cached rows — that's the global
+    /// stage's job once the candidates are merged.
+    is_partial: bool,
+    /// The index, within a cached row's order key, of the column backed by an input watermark.
+    /// `None` means state here grows unbounded by time; `Some` lets [`Self::evict_expired`]
+    /// drop rows that can provably never be emitted again.
+    watermark_order_col_idx: Option<usize>,
+    /// `Some(p)` means the effective cap is `p` percent of [`Self::group_cardinality`],
+    /// recomputed on every [`Self::insert`], instead of the fixed `limit` above.
+    limit_percent: Option<f64>,
+    /// Per order-key-column sort direction, taken from `TopNNode.order_by`: `order_desc[i]` is
+    /// true when the `i`-th order key column sorts `DESCENDING`. Ranks [`OrderedKey`]s so that
+    /// "worse" rows — the ones [`Self::insert`] evicts once over capacity — always sort last,
+    /// regardless of which columns are ascending or descending.
+    order_desc: Arc<[bool]>,
+    group_cardinality: u64,
+    entries: BTreeMap<(OrderedKey, Vec<ScalarImpl>), OwnedRow>,
+}
+
+impl TopNCache {
+    pub fn new(
+        limit: u64,
+        offset: u64,
+        with_ties: bool,
+        is_partial: bool,
+        watermark_order_col_idx: Option<usize>,
+        limit_percent: Option<f64>,
+        order_desc: Vec<bool>,
+    ) -> Self {
+        Self {
+            limit,
+            offset,
+            with_ties,
+            is_partial,
+            watermark_order_col_idx,
+            limit_percent,
+            order_desc: order_desc.into(),
+            group_cardinality: 0,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// The row count this cache retains right now: `limit_percent` percent of the current group's
+    /// cardinality when [`Self::limit_percent`] is set, else the fixed `limit`.
+    pub fn effective_limit(&self) -> u64 {
+        match self.limit_percent {
+            Some(percent) => {
+                ((self.group_cardinality as f64) * percent / 100.0).ceil() as u64
+            }
+            None => self.limit,
+        }
+    }
+
+    /// The maximum number of rows this cache should ever hold. A partial (local) node must still
+    /// retain `offset + limit` worth of candidates for the global merge stage to re-rank, even
+    /// though it never applies the offset itself.
+    pub fn capacity(&self) -> u64 {
+        if self.is_partial {
+            self.effective_limit()
+        } else {
+            self.offset + self.effective_limit()
+        }
+    }
+
+    /// Inserts a row under `order_key`/`pk`, tracking it against the group's cardinality (for
+    /// [`Self::effective_limit`]) and evicting the worst-ranked entry once over
+    /// [`Self::capacity`]. Ties at the eviction boundary are kept when `with_ties` is set.
+    pub fn insert(&mut self, order_key: Vec<ScalarImpl>, pk: Vec<ScalarImpl>, row: OwnedRow) {
+        self.group_cardinality += 1;
+        let key = OrderedKey {
+            values: order_key,
+            desc: self.order_desc.clone(),
+        };
+        self.entries.insert((key, pk), row);
+
+        let capacity = self.capacity() as usize;
+        while self.entries.len() > capacity {
+            let Some(boundary_key) = self.entries.keys().next_back().map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            if self.with_ties && self.entries.len() > 0 {
+                // Don't evict past `capacity` if doing so would split a group of ties at the
+                // boundary; remove only the strictly-worse entries first.
+                let tie_count = self
+                    .entries
+                    .keys()
+                    .rev()
+                    .take_while(|(k, _)| *k == boundary_key)
+                    .count();
+                if tie_count > 1 && self.entries.len() - tie_count < capacity {
+                    break;
+                }
+            }
+            let last = self.entries.keys().next_back().cloned();
+            if let Some(key) = last {
+                self.entries.remove(&key);
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Drops every cached row whose watermark-backed order column has fallen strictly below
+    /// `watermark` — rows an append-only, time-ordered `ORDER BY ... LIMIT` can never re-rank
+    /// above again, since later input can only arrive with a greater-or-equal watermark column.
+    /// A no-op when this node has no watermark-backed order column.
+    pub fn evict_expired(&mut self, watermark: &ScalarImpl) {
+        let Some(idx) = self.watermark_order_col_idx else {
+            return;
+        };
+        self.entries
+            .retain(|(order_key, _), _| &order_key.values[idx] >= watermark);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
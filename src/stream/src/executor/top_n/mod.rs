@@ -0,0 +1,55 @@
+// Copyright 2023 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Executor-side consumer of the `TopNNode` fields `StreamTopN::to_stream_prost_body` emits.
+//!
+//! The `ExecutorBuilder` that turns a `TopNNode` into a running executor lives outside this
+//! crate's slice of the tree; [`build_topn_cache`] is the seam it's expected to call into so the
+//! plan-side `is_partial`/`watermark_order_col_idx`/`limit_percent` fields actually drive
+//! behavior here, rather than only being round-tripped through the protobuf.
+
+mod cache;
+
+pub use cache::TopNCache;
+use risingwave_pb::common::order_type::Direction;
+use risingwave_pb::stream_plan::TopNNode;
+
+/// Builds the [`TopNCache`] a `TopNNode`'s executor should run with, translating its
+/// `is_partial`/`watermark_order_col_idx`/`limit_percent` fields into the cache's bounded-state,
+/// watermark-eviction, and percent-recompute behavior, and `order_by` into per-column ASC/DESC
+/// ranking so the cache doesn't assume every order key column sorts ascending.
+pub fn build_topn_cache(node: &TopNNode) -> TopNCache {
+    let order_desc = node
+        .order_by
+        .iter()
+        .map(|col| {
+            col.order_type
+                .as_ref()
+                .map(|order_type| order_type.direction() == Direction::Descending)
+                .unwrap_or(false)
+        })
+        .collect();
+    TopNCache::new(
+        node.limit,
+        node.offset,
+        node.with_ties,
+        node.is_partial,
+        // `TopNNode` carries the watermark column's index among the output columns, while the
+        // cache indexes into each row's order key, whose columns follow `order_by`.
+        node.watermark_order_col_idx
+            .and_then(|idx| node.order_by.iter().position(|col| col.column_index == idx)),
+        node.limit_percent,
+        order_desc,
+    )
+}
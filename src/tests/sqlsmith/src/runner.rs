@@ -14,6 +14,12 @@
 
 //! Provides E2E Test runner functionality.
 
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::hash::{Hash, Hasher};
+use std::io::Write as _;
+use std::time::Instant;
+
 use anyhow;
 use itertools::Itertools;
 use rand::rngs::SmallRng;
@@ -21,8 +27,9 @@ use rand::{Rng, SeedableRng};
 #[cfg(madsim)]
 use rand_chacha::ChaChaRng;
 use risingwave_common::error::anyhow_error;
+use serde::Serialize;
 use tokio_postgres::error::Error as PgError;
-use tokio_postgres::Client;
+use tokio_postgres::{Client, SimpleQueryMessage, Statement};
 
 use crate::validation::is_permissible_error;
 use crate::{
@@ -51,7 +58,8 @@ pub async fn run_pre_generated(client: &Client, outdir: &str) {
     for statement in parse_sql(&queries) {
         let sql = statement.to_string();
         tracing::info!("[EXECUTING STATEMENT]: {}", sql);
-        validate_response(&setup_sql, &sql, client.simple_query(&sql).await).unwrap();
+        let (result, _) = validate_response(&setup_sql, &sql, client.simple_query(&sql).await);
+        result.unwrap();
     }
 }
 
@@ -64,17 +72,28 @@ pub async fn generate(
     client: &Client,
     testdata: &str,
     count: usize,
-    _outdir: &str,
+    outdir: &str,
     seed: Option<u64>,
+    differential_testing: bool,
+    extended_protocol_testing: bool,
+    statement_log_sampling_rate: f64,
 ) {
     let mut rng = generate_rng(seed);
+    let logger = StatementLogger::new(outdir, seed, statement_log_sampling_rate);
     let (tables, base_tables, mviews, setup_sql) =
-        create_tables(&mut rng, testdata, client).await.unwrap();
+        create_tables(&mut rng, testdata, client, &logger).await.unwrap();
 
     let rows_per_table = 10;
     let max_rows_inserted = rows_per_table * base_tables.len();
 
-    let populate_sql = populate_tables(client, &mut rng, base_tables.clone(), rows_per_table).await;
+    let populate_sql = populate_tables(
+        client,
+        &mut rng,
+        base_tables.clone(),
+        rows_per_table,
+        &logger,
+    )
+    .await;
     let setup_sql = format!("{}\n{}", setup_sql, populate_sql);
     tracing::info!("Populated base tables");
 
@@ -85,6 +104,7 @@ pub async fn generate(
         &setup_sql,
         base_tables,
         max_rows_inserted,
+        &logger,
     )
     .await;
     tracing::info!("Passed sqlsmith tests");
@@ -95,8 +115,18 @@ pub async fn generate(
         let session_sql = test_session_variable(client, &mut rng).await;
         let sql = sql_gen(&mut rng, tables.clone());
         tracing::info!("[EXECUTING TEST_BATCH]: {}", sql);
+        let start = Instant::now();
         let response = client.simple_query(sql.as_str()).await;
-        match validate_response(&setup_sql, &format!("{};\n{};", session_sql, sql), response) {
+        let (result, error_code) =
+            validate_response(&setup_sql, &format!("{};\n{};", session_sql, sql), response);
+        logger.log(
+            StatementKind::Batch,
+            &sql,
+            &setup_sql,
+            start.elapsed(),
+            outcome_of(&result, error_code),
+        );
+        match result {
             Err(_e) => {
                 generated_queries += 1;
                 queries.push_str(&format!("-- {};\n", &sql));
@@ -107,19 +137,56 @@ pub async fn generate(
             Ok(skipped) if skipped == 0 => {
                 generated_queries += 1;
                 queries.push_str(&format!("{};\n", &sql));
+                if differential_testing
+                    && let Err(e) = test_differential_optimizer(client, &mut rng, &setup_sql, &sql).await
+                {
+                    tracing::error!("Differential optimizer mismatch encountered: {}", e);
+                    return;
+                }
             }
             _ => {}
         }
     }
     tracing::info!("Generated {} batch queries", generated_queries);
 
+    if extended_protocol_testing {
+        for cache_mode in [
+            PreparedStatementCacheMode::Unbounded,
+            PreparedStatementCacheMode::Disabled,
+        ] {
+            let extended_queries = test_extended_protocol_queries(
+                client,
+                &mut rng,
+                tables.clone(),
+                &setup_sql,
+                count,
+                cache_mode,
+                &logger,
+            )
+            .await
+            .unwrap();
+            queries.push_str(&extended_queries);
+        }
+        tracing::info!("Generated extended protocol queries");
+    }
+
     let mut generated_queries = 0;
     for _ in 0..count {
         let session_sql = test_session_variable(client, &mut rng).await;
         let (sql, table) = mview_sql_gen(&mut rng, tables.clone(), "stream_query");
         tracing::info!("[EXECUTING TEST_STREAM]: {}", sql);
+        let start = Instant::now();
         let response = client.simple_query(&sql).await;
-        match validate_response(&setup_sql, &format!("{};\n{};", session_sql, sql), response) {
+        let (result, error_code) =
+            validate_response(&setup_sql, &format!("{};\n{};", session_sql, sql), response);
+        logger.log(
+            StatementKind::Mview,
+            &sql,
+            &setup_sql,
+            start.elapsed(),
+            outcome_of(&result, error_code),
+        );
+        match result {
             Err(_e) => {
                 generated_queries += 1;
                 queries.push_str(&format!("-- {};\n", &sql));
@@ -144,10 +211,19 @@ pub async fn generate(
 }
 
 /// e2e test runner for sqlsmith
-pub async fn run(client: &Client, testdata: &str, count: usize, seed: Option<u64>) {
+pub async fn run(
+    client: &Client,
+    testdata: &str,
+    count: usize,
+    seed: Option<u64>,
+    differential_testing: bool,
+    outdir: &str,
+    statement_log_sampling_rate: f64,
+) {
     let mut rng = generate_rng(seed);
+    let logger = StatementLogger::new(outdir, seed, statement_log_sampling_rate);
     let (tables, base_tables, mviews, mut setup_sql) =
-        create_tables(&mut rng, testdata, client).await.unwrap();
+        create_tables(&mut rng, testdata, client, &logger).await.unwrap();
     tracing::info!("Created tables");
 
     let session_sql = set_variable(client, "RW_IMPLICIT_FLUSH", "TRUE").await;
@@ -157,7 +233,14 @@ pub async fn run(client: &Client, testdata: &str, count: usize, seed: Option<u64
     tracing::info!("Set session variables");
 
     let rows_per_table = 10;
-    let populate_sql = populate_tables(client, &mut rng, base_tables.clone(), rows_per_table).await;
+    let populate_sql = populate_tables(
+        client,
+        &mut rng,
+        base_tables.clone(),
+        rows_per_table,
+        &logger,
+    )
+    .await;
     let setup_sql = format!("{}\n{}", setup_sql, populate_sql);
     tracing::info!("Populated base tables");
 
@@ -170,14 +253,23 @@ pub async fn run(client: &Client, testdata: &str, count: usize, seed: Option<u64
         &setup_sql,
         base_tables,
         max_rows_inserted,
+        &logger,
     )
     .await;
     tracing::info!("Passed sqlsmith tests");
-    test_batch_queries(client, &mut rng, tables.clone(), &setup_sql, count)
-        .await
-        .unwrap();
+    test_batch_queries(
+        client,
+        &mut rng,
+        tables.clone(),
+        &setup_sql,
+        count,
+        differential_testing,
+        &logger,
+    )
+    .await
+    .unwrap();
     tracing::info!("Passed batch queries");
-    test_stream_queries(client, &mut rng, tables.clone(), &setup_sql, count)
+    test_stream_queries(client, &mut rng, tables.clone(), &setup_sql, count, &logger)
         .await
         .unwrap();
     tracing::info!("Passed stream queries");
@@ -206,11 +298,20 @@ async fn populate_tables<R: Rng>(
     rng: &mut R,
     base_tables: Vec<Table>,
     row_count: usize,
+    logger: &StatementLogger,
 ) -> String {
     let inserts = insert_sql_gen(rng, base_tables, row_count);
     for insert in &inserts {
         tracing::info!("[EXECUTING INSERT]: {}", insert);
+        let start = Instant::now();
         client.simple_query(insert).await.unwrap();
+        logger.log(
+            StatementKind::Insert,
+            insert,
+            "",
+            start.elapsed(),
+            StatementOutcome::Ok,
+        );
     }
     inserts.into_iter().map(|i| format!("{};\n", i)).collect()
 }
@@ -223,6 +324,7 @@ async fn test_sqlsmith<R: Rng>(
     setup_sql: &str,
     base_tables: Vec<Table>,
     row_count: usize,
+    logger: &StatementLogger,
 ) {
     // Test inserted rows should be at least 50% population count,
     // otherwise we don't have sufficient data in our system.
@@ -234,10 +336,17 @@ async fn test_sqlsmith<R: Rng>(
     let threshold = 0.40; // permit at most 40% of queries to be skipped.
     let sample_size = 50;
 
-    let skipped_percentage =
-        test_batch_queries(client, rng, tables.clone(), setup_sql, sample_size)
-            .await
-            .unwrap();
+    let skipped_percentage = test_batch_queries(
+        client,
+        rng,
+        tables.clone(),
+        setup_sql,
+        sample_size,
+        false,
+        logger,
+    )
+    .await
+    .unwrap();
     tracing::info!(
         "percentage of skipped batch queries = {}, threshold: {}",
         skipped_percentage,
@@ -248,7 +357,7 @@ async fn test_sqlsmith<R: Rng>(
     }
 
     let skipped_percentage =
-        test_stream_queries(client, rng, tables.clone(), setup_sql, sample_size)
+        test_stream_queries(client, rng, tables.clone(), setup_sql, sample_size, logger)
             .await
             .unwrap();
     tracing::info!(
@@ -268,6 +377,13 @@ async fn set_variable(client: &Client, variable: &str, value: &str) -> String {
     s
 }
 
+/// Resets `variable` back to its session default, undoing a prior [`set_variable`] call.
+async fn reset_variable(client: &Client, variable: &str) {
+    let s = format!("RESET {variable};");
+    tracing::info!("[EXECUTING RESET_VAR]: {}", s);
+    client.simple_query(&s).await.unwrap();
+}
+
 async fn test_session_variable<R: Rng>(client: &Client, rng: &mut R) -> String {
     let session_sql = session_sql_gen(rng);
     tracing::info!("[EXECUTING TEST SESSION_VAR]: {}", session_sql);
@@ -302,18 +418,160 @@ async fn test_batch_queries<R: Rng>(
     tables: Vec<Table>,
     setup_sql: &str,
     sample_size: usize,
+    differential_testing: bool,
+    logger: &StatementLogger,
 ) -> Result<f64> {
     let mut skipped = 0;
     for _ in 0..sample_size {
         let session_sql = test_session_variable(client, rng).await;
         let sql = sql_gen(rng, tables.clone());
         tracing::info!("[EXECUTING TEST_BATCH]: {}", sql);
+        let start = Instant::now();
         let response = client.simple_query(sql.as_str()).await;
-        skipped += validate_response(setup_sql, &format!("{};\n{};", session_sql, sql), response)?;
+        let (result, error_code) =
+            validate_response(setup_sql, &format!("{};\n{};", session_sql, sql), response);
+        logger.log(
+            StatementKind::Batch,
+            &sql,
+            setup_sql,
+            start.elapsed(),
+            outcome_of(&result, error_code),
+        );
+        let this_skipped = result?;
+        if this_skipped == 0 && differential_testing {
+            test_differential_optimizer(client, rng, setup_sql, &sql).await?;
+        }
+        skipped += this_skipped;
     }
     Ok(skipped as f64 / sample_size as f64)
 }
 
+/// Prepared-statement cache configurations exercised by [`test_extended_protocol_queries`],
+/// inspired by Diesel's per-connection `CacheSize` control. Every query in this runner otherwise
+/// goes through `simple_query`, so the `PREPARE`/`EXECUTE` (extended protocol) path — parameter
+/// binding, type inference, and statement-cache reuse/eviction — is never exercised.
+#[derive(Debug, Clone, Copy)]
+enum PreparedStatementCacheMode {
+    /// Generated statements are kept in a local cache keyed by SQL text, so repeats reuse the
+    /// same `Statement` and stress the cache-hit path.
+    Unbounded,
+    /// Every generated statement is prepared fresh, stressing the cold-prepare path that
+    /// `Unbounded` never takes once its cache is warm.
+    Disabled,
+}
+
+/// Generates parameterized statements (a single `$1` placeholder bound to an extracted integer
+/// literal) and executes them through `client.prepare`/`client.query` rather than
+/// `client.simple_query`, under the given prepared-statement-cache configuration. Successful
+/// statements are returned in replayable `PREPARE ... EXECUTE` form.
+async fn test_extended_protocol_queries<R: Rng>(
+    client: &Client,
+    rng: &mut R,
+    tables: Vec<Table>,
+    setup_sql: &str,
+    sample_size: usize,
+    cache_mode: PreparedStatementCacheMode,
+    logger: &StatementLogger,
+) -> Result<String> {
+    let mut cache: HashMap<String, Statement> = HashMap::new();
+    let mut queries = String::new();
+    for _ in 0..sample_size {
+        let sql = sql_gen(rng, tables.clone());
+        let Some((parameterized_sql, param)) = parameterize_one_literal(&sql) else {
+            continue;
+        };
+        tracing::info!(
+            "[EXECUTING TEST_EXTENDED]: {} with $1 = {}",
+            parameterized_sql,
+            param
+        );
+        let start = Instant::now();
+        let prepared = match cache_mode {
+            PreparedStatementCacheMode::Unbounded => match cache.get(&parameterized_sql) {
+                Some(stmt) => Ok(stmt.clone()),
+                None => {
+                    let stmt = client.prepare(&parameterized_sql).await;
+                    if let Ok(stmt) = &stmt {
+                        cache.insert(parameterized_sql.clone(), stmt.clone());
+                    }
+                    stmt
+                }
+            },
+            PreparedStatementCacheMode::Disabled => client.prepare(&parameterized_sql).await,
+        };
+        let response = match prepared {
+            Ok(stmt) => client.query(&stmt, &[&param]).await.map(|_rows| ()),
+            Err(e) => Err(e),
+        };
+        let (result, error_code) = validate_response(setup_sql, &parameterized_sql, response);
+        logger.log(
+            StatementKind::Batch,
+            &parameterized_sql,
+            setup_sql,
+            start.elapsed(),
+            outcome_of(&result, error_code),
+        );
+        if result? == 0 {
+            queries.push_str(&format!(
+                "PREPARE extended_stmt AS {};\nEXECUTE extended_stmt({});\nDEALLOCATE extended_stmt;\n",
+                parameterized_sql, param
+            ));
+        }
+    }
+    Ok(queries)
+}
+
+/// Replaces the first standalone integer literal in `sql` with a `$1` placeholder, returning the
+/// rewritten SQL and the extracted value. Returns `None` if `sql` has no such literal (e.g. it
+/// only references identifiers or table names that happen to contain digits).
+fn parameterize_one_literal(sql: &str) -> Option<(String, i32)> {
+    let bytes = sql.as_bytes();
+    let mut i = 0;
+    let mut in_string = false;
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            // A doubled quote inside a string is an escaped literal quote, not the end of the
+            // span, so it doesn't toggle `in_string`.
+            if in_string && i + 1 < bytes.len() && bytes[i + 1] == b'\'' {
+                i += 2;
+                continue;
+            }
+            in_string = !in_string;
+            i += 1;
+            continue;
+        }
+        if in_string {
+            // Never parameterize digits inside a string literal (e.g. the `2024` in
+            // `'2024-01-01'::date`) — rewriting those produces malformed SQL, not a bind param.
+            i += 1;
+            continue;
+        }
+        let preceded_by_word = i > 0 && is_word_byte(bytes[i - 1]);
+        // A digit run directly after a `.` is the fractional half of a float literal (e.g. the
+        // `14` in `3.14`), not a standalone integer — rewriting just that half produces malformed
+        // SQL like `3.$1`. The leading half is already excluded by `followed_by_word` below.
+        let preceded_by_dot = i > 0 && bytes[i - 1] == b'.';
+        if bytes[i].is_ascii_digit() && !preceded_by_word && !preceded_by_dot {
+            let start = i;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            let followed_by_word = i < bytes.len() && (is_word_byte(bytes[i]) || bytes[i] == b'.');
+            if !followed_by_word && let Ok(value) = sql[start..i].parse::<i32>() {
+                let rewritten = format!("{}$1{}", &sql[..start], &sql[i..]);
+                return Some((rewritten, value));
+            }
+            continue;
+        }
+        i += 1;
+    }
+    None
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
 /// Test stream queries, returns skipped query statistics
 async fn test_stream_queries<R: Rng>(
     client: &Client,
@@ -321,14 +579,25 @@ async fn test_stream_queries<R: Rng>(
     tables: Vec<Table>,
     setup_sql: &str,
     sample_size: usize,
+    logger: &StatementLogger,
 ) -> Result<f64> {
     let mut skipped = 0;
     for _ in 0..sample_size {
         let session_sql = test_session_variable(client, rng).await;
         let (sql, table) = mview_sql_gen(rng, tables.clone(), "stream_query");
         tracing::info!("[EXECUTING TEST_STREAM]: {}", sql);
+        let start = Instant::now();
         let response = client.simple_query(&sql).await;
-        skipped += validate_response(setup_sql, &format!("{};\n{};", session_sql, sql), response)?;
+        let (result, error_code) =
+            validate_response(setup_sql, &format!("{};\n{};", session_sql, sql), response);
+        logger.log(
+            StatementKind::Mview,
+            &sql,
+            setup_sql,
+            start.elapsed(),
+            outcome_of(&result, error_code),
+        );
+        skipped += result?;
         tracing::info!("[EXECUTING DROP MVIEW]: {}", &format_drop_mview(&table));
         drop_mview_table(&table, client).await;
     }
@@ -349,6 +618,7 @@ async fn create_tables(
     rng: &mut impl Rng,
     testdata: &str,
     client: &Client,
+    logger: &StatementLogger,
 ) -> Result<(Vec<Table>, Vec<Table>, Vec<Table>, String)> {
     tracing::info!("Preparing tables...");
 
@@ -365,7 +635,15 @@ async fn create_tables(
     for stmt in &statements {
         let create_sql = stmt.to_string();
         tracing::info!("[EXECUTING CREATE TABLE]: {}", &create_sql);
+        let start = Instant::now();
         client.simple_query(&create_sql).await.unwrap();
+        logger.log(
+            StatementKind::Ddl,
+            &create_sql,
+            "",
+            start.elapsed(),
+            StatementOutcome::Ok,
+        );
         setup_sql.push_str(&format!("{};\n", &create_sql));
     }
 
@@ -375,8 +653,17 @@ async fn create_tables(
         let (create_sql, table) =
             mview_sql_gen(rng, mvs_and_base_tables.clone(), &format!("m{}", i));
         tracing::info!("[EXECUTING CREATE MVIEW]: {}", &create_sql);
+        let start = Instant::now();
         let response = client.simple_query(&create_sql).await;
-        let skip_count = validate_response(&setup_sql, &create_sql, response)?;
+        let (skip_count, error_code) = validate_response(&setup_sql, &create_sql, response);
+        logger.log(
+            StatementKind::Ddl,
+            &create_sql,
+            &setup_sql,
+            start.elapsed(),
+            outcome_of(&skip_count, error_code),
+        );
+        let skip_count = skip_count?;
         if skip_count == 0 {
             setup_sql.push_str(&format!("{};\n", &create_sql));
             mvs_and_base_tables.push(table.clone());
@@ -435,22 +722,253 @@ Reason:
     )
 }
 
-/// Validate client responses, returning a count of skipped queries.
-fn validate_response<_Row>(setup_sql: &str, query: &str, response: PgResult<_Row>) -> Result<i64> {
+/// Validate client responses, returning a count of skipped queries and, when a query was
+/// skipped, the SQLSTATE code that made it permissible (used by [`StatementLogger`]).
+fn validate_response<_Row>(
+    setup_sql: &str,
+    query: &str,
+    response: PgResult<_Row>,
+) -> (Result<i64>, Option<String>) {
     match response {
-        Ok(_) => Ok(0),
+        Ok(_) => (Ok(0), None),
         Err(e) => {
             // Permit runtime errors conservatively.
             if let Some(e) = e.as_db_error()
                 && is_permissible_error(&e.to_string())
             {
                 tracing::info!("[SKIPPED ERROR]: {:?}", e);
-                return Ok(1);
+                let code = e.code().code().to_string();
+                return (Ok(1), Some(code));
             }
             // consolidate error reason for deterministic test
             let error_msg = format_fail_reason(setup_sql, query, &e);
             tracing::info!("{}", error_msg);
+            (Err(anyhow_error!(error_msg)), None)
+        }
+    }
+}
+
+/// Builds the [`StatementOutcome`] a [`StatementLogger`] should record for a
+/// [`validate_response`] result, without consuming it.
+fn outcome_of(result: &Result<i64>, error_code: Option<String>) -> StatementOutcome {
+    match result {
+        Ok(0) => StatementOutcome::Ok,
+        Ok(_) => StatementOutcome::SkippedWithErrorCode {
+            error_code: error_code.unwrap_or_else(|| "unknown".to_string()),
+        },
+        Err(e) => StatementOutcome::UnexpectedFailure {
+            reason: e.to_string(),
+        },
+    }
+}
+
+/// Kind of statement recorded by [`StatementLogger`], matching the phases the runner executes
+/// statements in.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum StatementKind {
+    Ddl,
+    Insert,
+    Batch,
+    Mview,
+}
+
+/// Outcome of executing a logged statement.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+enum StatementOutcome {
+    Ok,
+    SkippedWithErrorCode { error_code: String },
+    UnexpectedFailure { reason: String },
+}
+
+#[derive(Serialize)]
+struct StatementLogRecord<'a> {
+    seed: Option<u64>,
+    kind: StatementKind,
+    sql: &'a str,
+    latency_ms: u128,
+    setup_sql_hash: u64,
+    outcome: StatementOutcome,
+}
+
+/// Samples and appends a structured JSON-lines execution log to `{outdir}/statement_log.jsonl`,
+/// so a failing CI run can be triaged and a single offending statement replayed deterministically
+/// from its seed, rather than grepping free-form `tracing` output.
+struct StatementLogger {
+    path: String,
+    seed: Option<u64>,
+    /// Fraction of statements to record, in `[0, 1]`, so long runs don't explode the log on disk.
+    sampling_rate: f64,
+    /// Its own RNG, independent of the generation `rng` the caller is threading through
+    /// `sql_gen`/friends — sampling off of the shared rng would perturb the deterministic
+    /// sequence of generated statements for a given `seed` every time the sample rate changed,
+    /// defeating "replay the same statements from this seed". Behind a `Mutex` since `log` is
+    /// called through a shared `&StatementLogger`.
+    sample_rng: std::sync::Mutex<SmallRng>,
+}
+
+impl StatementLogger {
+    fn new(outdir: &str, seed: Option<u64>, sampling_rate: f64) -> Self {
+        Self {
+            path: format!("{}/statement_log.jsonl", outdir),
+            seed,
+            sampling_rate,
+            sample_rng: std::sync::Mutex::new(SmallRng::from_entropy()),
+        }
+    }
+
+    fn log(
+        &self,
+        kind: StatementKind,
+        sql: &str,
+        setup_sql: &str,
+        latency: std::time::Duration,
+        outcome: StatementOutcome,
+    ) {
+        if self.sampling_rate < 1.0 {
+            let sampled_in = self
+                .sample_rng
+                .lock()
+                .unwrap()
+                .gen_bool(self.sampling_rate.clamp(0.0, 1.0));
+            if !sampled_in {
+                return;
+            }
+        }
+        let record = StatementLogRecord {
+            seed: self.seed,
+            kind,
+            sql,
+            latency_ms: latency.as_millis(),
+            setup_sql_hash: hash_setup_sql(setup_sql),
+            outcome,
+        };
+        let Ok(line) = serde_json::to_string(&record) else {
+            return;
+        };
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&self.path) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+fn hash_setup_sql(setup_sql: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    setup_sql.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Session variables toggling optimizer rewrites, paired with the "on"/"off" values to compare
+/// a generated query's result set across. Chosen from rewrites that only change the *plan*, not
+/// the semantics, so the two sides of the toggle must agree on every row.
+const DIFFERENTIAL_TOGGLES: &[(&str, &str, &str)] = &[
+    ("RW_ENABLE_TWO_PHASE_AGG", "TRUE", "FALSE"),
+    ("RW_ENABLE_JOIN_ORDERING", "TRUE", "FALSE"),
+    ("RW_FORCE_SPLIT_DISTINCT_AGG", "TRUE", "FALSE"),
+];
+
+/// Runs `sql` once per side of a randomly chosen optimizer toggle and fails if the two result
+/// sets disagree. This is the correctness counterpart to [`validate_response`]: that function
+/// only checks a query didn't error, this checks the optimizer didn't silently change the answer.
+/// Either side hitting a permissible runtime error skips the comparison rather than failing it.
+async fn test_differential_optimizer<R: Rng>(
+    client: &Client,
+    rng: &mut R,
+    setup_sql: &str,
+    sql: &str,
+) -> Result<()> {
+    let (variable, on, off) = DIFFERENTIAL_TOGGLES[rng.gen_range(0..DIFFERENTIAL_TOGGLES.len())];
+
+    set_variable(client, variable, on).await;
+    let on_result = skip_or_collect(setup_sql, sql, client.simple_query(sql).await);
+
+    set_variable(client, variable, off).await;
+    let off_result = skip_or_collect(setup_sql, sql, client.simple_query(sql).await);
+
+    // Always undo the toggle before this query's outcome propagates — on a permissible-error
+    // skip, a mismatch, or a hard error from `?` below — so it doesn't stay pinned at `off` for
+    // every later query in the run and silently change their plans too.
+    reset_variable(client, variable).await;
+
+    let Some(on_rows) = on_result? else {
+        return Ok(());
+    };
+    let Some(off_rows) = off_result? else {
+        return Ok(());
+    };
+
+    if on_rows != off_rows {
+        let error_msg = format!(
+            "
+[DIFFERENTIAL OPTIMIZER MISMATCH]: toggling `{variable}` between {on} and {off} changed the \
+result of:
+-- Setup
+{setup_sql}
+-- Query
+{sql}
+-- With {variable} = {on}
+{on_rows:#?}
+-- With {variable} = {off}
+{off_rows:#?}
+"
+        );
+        tracing::info!("{}", error_msg);
+        return Err(anyhow_error!(error_msg));
+    }
+    Ok(())
+}
+
+/// Collects and canonicalizes a successful response's rows, or returns `Ok(None)` if the query
+/// hit a permissible runtime error (comparison should be skipped), propagating any other error.
+fn skip_or_collect(
+    setup_sql: &str,
+    query: &str,
+    response: PgResult<Vec<SimpleQueryMessage>>,
+) -> Result<Option<Vec<String>>> {
+    match response {
+        Ok(rows) => Ok(Some(canonicalize_rows(rows))),
+        Err(e) => {
+            if let Some(e) = e.as_db_error()
+                && is_permissible_error(&e.to_string())
+            {
+                tracing::info!("[SKIPPED ERROR]: {:?}", e);
+                return Ok(None);
+            }
+            let error_msg = format_fail_reason(setup_sql, query, &e);
+            tracing::info!("{}", error_msg);
             Err(anyhow_error!(error_msg))
         }
     }
 }
+
+/// Canonicalizes a result set so two equivalent executions compare equal regardless of row
+/// order: render each row as a `|`-joined string, normalizing float formatting (e.g. `1` vs
+/// `1.0`), then sort the rows lexicographically.
+fn canonicalize_rows(rows: Vec<SimpleQueryMessage>) -> Vec<String> {
+    let mut rendered = rows
+        .into_iter()
+        .filter_map(|msg| match msg {
+            SimpleQueryMessage::Row(row) => Some(
+                (0..row.len())
+                    .map(|i| match row.get(i) {
+                        Some(s) => normalize_float_repr(s),
+                        None => "NULL".to_string(),
+                    })
+                    .join("|"),
+            ),
+            _ => None,
+        })
+        .collect_vec();
+    rendered.sort();
+    rendered
+}
+
+/// Best-effort float normalization: if a cell parses as a float, re-render it via its `Display`
+/// impl so differing trailing-zero formatting doesn't cause a spurious mismatch.
+fn normalize_float_repr(s: &str) -> String {
+    match s.parse::<f64>() {
+        Ok(f) => f.to_string(),
+        Err(_) => s.to_string(),
+    }
+}